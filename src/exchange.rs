@@ -0,0 +1,188 @@
+//! Multi-hop currency conversion via a directed rate graph.
+//!
+//! Rates loaded from different provider files don't necessarily share a
+//! common base currency. `Exchange` stores every known directed pair
+//! (`from -> to`, `add_or_update_rate`) as an edge in a graph, together
+//! with its inverse `1 / rate`, similar in spirit to rusty-money's
+//! `Exchange`/`ExchangeRate`. `get_rate` then finds the best conversion
+//! path between two currencies with Bellman-Ford: each edge is weighted
+//! `-ln(rate)`, so the shortest-weight path corresponds to the path that
+//! maximizes the product of rates, and the effective rate is
+//! `exp(-dist)`. Bellman-Ford (rather than Dijkstra) is required because
+//! `-ln(rate)` is negative whenever `rate > 1.0`. A negative-weight cycle
+//! reachable from the source means an arbitrage loop exists and is
+//! reported instead of silently used.
+
+use std::collections::HashMap;
+
+/// The result of looking up a conversion rate between two currencies.
+#[derive(Debug)]
+pub enum ConversionResult {
+    /// A path was found; `rate` converts 1 unit of `from` into `rate`
+    /// units of `to`, and `path` lists the currencies visited in order
+    /// (including `from` and `to`).
+    Rate { rate: f64, path: Vec<String> },
+    /// `from` and `to` are in disconnected components of the graph.
+    NoPath,
+    /// A negative-weight cycle reachable from `from` was found, i.e. an
+    /// arbitrage loop. The listed currencies form the loop.
+    ArbitrageDetected(Vec<String>),
+}
+
+/// A directed graph of known exchange rate pairs, allowing conversion
+/// between currencies that aren't directly adjacent.
+#[derive(Clone, Debug, Default)]
+pub struct Exchange {
+    // adjacency list: from -> Vec<(to, rate)>
+    edges: HashMap<String, Vec<(String, f64)>>,
+}
+
+impl Exchange {
+    pub fn new() -> Exchange {
+        Exchange { edges: HashMap::new() }
+    }
+
+    /// Records (or updates) the directed pair `from -> to` with the given
+    /// rate, and its inverse `to -> from` with `1.0 / rate`. Opposite
+    /// directions added separately for the same pair are deduplicated by
+    /// updating the existing edge rather than appending a new one.
+    pub fn add_or_update_rate(&mut self, from: &str, to: &str, rate: f64) {
+        if from == to || rate <= 0.0 {
+            return;
+        }
+        Self::upsert_edge(&mut self.edges, from, to, rate);
+        Self::upsert_edge(&mut self.edges, to, from, 1.0 / rate);
+    }
+
+    fn upsert_edge(edges: &mut HashMap<String, Vec<(String, f64)>>, from: &str, to: &str, rate: f64) {
+        let neighbours = edges.entry(from.to_string()).or_insert_with(Vec::new);
+        match neighbours.iter_mut().find(|(n, _)| n == to) {
+            Some(entry) => entry.1 = rate,
+            None => neighbours.push((to.to_string(), rate)),
+        }
+    }
+
+    /// Finds the best conversion path from `from` to `to`, maximizing the
+    /// product of rates along the path, using Bellman-Ford over
+    /// `-ln(rate)` edge weights.
+    pub fn get_rate(&self, from: &str, to: &str) -> ConversionResult {
+        if !self.edges.contains_key(from) {
+            return ConversionResult::NoPath;
+        }
+
+        let mut dist: HashMap<&str, f64> = HashMap::new();
+        let mut pred: HashMap<&str, &str> = HashMap::new();
+        dist.insert(from, 0.0);
+
+        let node_count = self.edges.len();
+
+        // standard Bellman-Ford relaxation, |V| - 1 passes
+        for _ in 0..node_count.saturating_sub(1) {
+            let mut changed = false;
+            for (u, neighbours) in self.edges.iter() {
+                let Some(&du) = dist.get(u.as_str()) else { continue };
+                for (v, rate) in neighbours {
+                    let weight = -rate.ln();
+                    let dv = du + weight;
+                    if dv < *dist.get(v.as_str()).unwrap_or(&f64::INFINITY) {
+                        dist.insert(v.as_str(), dv);
+                        pred.insert(v.as_str(), u.as_str());
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        // one more pass to detect a negative-weight cycle reachable from `from`.
+        // A small negative epsilon absorbs floating-point rounding noise: a
+        // reciprocal pair's `-ln(rate)` and `-ln(1.0 / rate)` don't cancel to
+        // bit-exact zero, so a strict `< 0` comparison flags ordinary
+        // non-arbitrage rates as cycles essentially every time.
+        const CYCLE_EPSILON: f64 = -1e-9;
+        for (u, neighbours) in self.edges.iter() {
+            let Some(&du) = dist.get(u.as_str()) else { continue };
+            for (v, rate) in neighbours {
+                let weight = -rate.ln();
+                if du + weight < *dist.get(v.as_str()).unwrap_or(&f64::INFINITY) + CYCLE_EPSILON {
+                    // `v` is on (or reachable from) a negative cycle; walking
+                    // back |V| more predecessor steps first guarantees we
+                    // land inside the cycle before tracing it.
+                    let mut on_cycle = v.as_str();
+                    for _ in 0..node_count {
+                        on_cycle = pred.get(on_cycle).copied().unwrap_or(on_cycle);
+                    }
+                    return ConversionResult::ArbitrageDetected(Self::trace_cycle(&pred, on_cycle));
+                }
+            }
+        }
+
+        if !dist.contains_key(to) {
+            return ConversionResult::NoPath;
+        }
+
+        let mut path = vec![to.to_string()];
+        let mut cur = to;
+        while cur != from {
+            match pred.get(cur) {
+                Some(&p) => {
+                    path.push(p.to_string());
+                    cur = p;
+                }
+                None => break,
+            }
+        }
+        path.reverse();
+
+        ConversionResult::Rate { rate: (-dist[to]).exp(), path }
+    }
+
+    /// Walks `pred` backwards from a node known to lie on (or lead into)
+    /// a negative-weight cycle until it repeats, returning the cycle.
+    fn trace_cycle<'a>(pred: &HashMap<&'a str, &'a str>, start: &'a str) -> Vec<String> {
+        let mut seen = Vec::new();
+        let mut cur = start;
+        loop {
+            if seen.iter().any(|c: &String| c == cur) {
+                break;
+            }
+            seen.push(cur.to_string());
+            match pred.get(cur) {
+                Some(&p) => cur = p,
+                None => break,
+            }
+        }
+        seen
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn realistic_rate_table_is_not_flagged_as_arbitrage() {
+        let mut exchange = Exchange::new();
+        exchange.add_or_update_rate("EUR", "USD", 1.0843);
+        exchange.add_or_update_rate("EUR", "JPY", 160.233);
+        exchange.add_or_update_rate("EUR", "GBP", 0.8567);
+        exchange.add_or_update_rate("EUR", "CHF", 0.9432);
+        exchange.add_or_update_rate("EUR", "PLN", 4.31);
+
+        match exchange.get_rate("EUR", "USD") {
+            ConversionResult::Rate { rate, .. } => assert!((rate - 1.0843).abs() < 1e-6),
+            other => panic!("expected a direct rate, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn disconnected_currencies_report_no_path() {
+        let mut exchange = Exchange::new();
+        exchange.add_or_update_rate("EUR", "USD", 1.0843);
+        exchange.add_or_update_rate("GBP", "JPY", 186.0);
+
+        assert!(matches!(exchange.get_rate("EUR", "JPY"), ConversionResult::NoPath));
+    }
+}
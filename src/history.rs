@@ -0,0 +1,236 @@
+//! Historical exchange rate snapshots.
+//!
+//! The provider also serves dated snapshots besides `latest.json`. Each
+//! day's rates are cached under a date-keyed filename
+//! (`currency-YYYY-MM-DD.json`) in the temp dir, instead of overwriting
+//! the single file used for the latest rates, so looking up the same
+//! date twice doesn't require downloading it again.
+//!
+//! Unlike the latest-rates path in `providers`, dated snapshots always
+//! come from the wahrungsrechner history endpoint; `--provider` has no
+//! effect here, since the other providers don't expose a history API.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::io::prelude::*;
+use std::path::Path;
+use curl::easy::Easy;
+use serde_json::Value;
+
+const DATED_DL_ADDR_TEMPLATE: &str = "https://cdn.wahrungsrechner.info/api/history/{date}.json";
+
+/// A calendar date, just precise enough to name and iterate over the
+/// provider's dated snapshots without pulling in a date/time crate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date {
+    pub year: i32,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl Date {
+    /// Parses a `YYYY-MM-DD` date, rejecting out-of-range months/days.
+    pub fn parse(s: &str) -> Option<Date> {
+        let parts: Vec<&str> = s.split('-').collect();
+        if parts.len() != 3 {
+            return None;
+        }
+
+        let year = parts[0].parse::<i32>().ok()?;
+        let month = parts[1].parse::<u8>().ok()?;
+        let day = parts[2].parse::<u8>().ok()?;
+
+        if !(1..=12).contains(&month) || day < 1 || day > Self::days_in_month(year, month) {
+            return None;
+        }
+
+        Some(Date { year, month, day })
+    }
+
+    fn is_leap_year(year: i32) -> bool {
+        (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+    }
+
+    fn days_in_month(year: i32, month: u8) -> u8 {
+        match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 => if Self::is_leap_year(year) { 29 } else { 28 },
+            _ => 30,
+        }
+    }
+
+    /// Returns the number of seconds since the Unix epoch for midnight
+    /// UTC on this date, using Howard Hinnant's `days_from_civil`
+    /// algorithm, so snapshot dates can be compared against `timestamp`
+    /// fields without pulling in a date/time crate.
+    pub fn to_epoch_seconds(&self) -> u64 {
+        let y = if self.month <= 2 { self.year as i64 - 1 } else { self.year as i64 };
+        let era = if y >= 0 { y } else { y - 399 } / 400;
+        let yoe = (y - era * 400) as i64;
+        let mp = ((self.month as i64 + 9) % 12) as i64;
+        let doy = (153 * mp + 2) / 5 + self.day as i64 - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        let days_since_epoch = era * 146_097 + doe - 719_468;
+        (days_since_epoch * 86_400).max(0) as u64
+    }
+
+    /// Returns the next calendar day.
+    pub fn next(&self) -> Date {
+        let days = Self::days_in_month(self.year, self.month);
+        if self.day < days {
+            Date { day: self.day + 1, ..*self }
+        } else if self.month < 12 {
+            Date { month: self.month + 1, day: 1, ..*self }
+        } else {
+            Date { year: self.year + 1, month: 1, day: 1 }
+        }
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+fn dated_filename(date: Date) -> String {
+    format!("currency-{}.json", date)
+}
+
+fn dated_download_url(date: Date) -> String {
+    DATED_DL_ADDR_TEMPLATE.replace("{date}", &date.to_string())
+}
+
+/// Makes sure the dated snapshot for `date` exists on disk in
+/// `temp_dir`, downloading it if it's missing.
+pub fn ensure_dated_rates_file(temp_dir: &str, date: Date) -> bool {
+    let file_name = Path::new(temp_dir).join(dated_filename(date));
+    if file_name.exists() {
+        return true;
+    }
+    download_dated_rates_file(temp_dir, date)
+}
+
+/// Downloads the dated snapshot for `date` into memory first and only
+/// writes it to the cache file once the whole download has succeeded, so a
+/// failed download (network blip, interrupted process) never leaves a
+/// 0-byte stub behind that `ensure_dated_rates_file` would mistake for a
+/// real cached snapshot on every later run.
+fn download_dated_rates_file(temp_dir: &str, date: Date) -> bool {
+    let mut handle = Easy::new();
+    if handle.url(&dated_download_url(date)).is_err() {
+        eprintln!("Invalid historical rates URL for {}.", date);
+        return false;
+    }
+
+    let mut buffer: Vec<u8> = Vec::new();
+    {
+        let mut transfer = handle.transfer();
+        if transfer.write_function(|data| {
+            buffer.extend_from_slice(data);
+            Ok(data.len())
+        }).is_err() {
+            return false;
+        }
+
+        if let Err(err) = transfer.perform() {
+            eprintln!("Error while downloading historical rates for {}: {}", date, err);
+            return false;
+        }
+    }
+
+    let file_name = Path::new(temp_dir).join(dated_filename(date));
+    let file = match File::create(&file_name) {
+        Ok(file) => file,
+        Err(err) => {
+            eprintln!("Couldn't create {} (error: {}).", file_name.display(), err);
+            return false;
+        },
+    };
+
+    let mut writer = BufWriter::new(file);
+    writer.write_all(&buffer).is_ok()
+}
+
+/// Loads the cached snapshot for `date` from `temp_dir`, if present.
+pub fn load_dated_rates(temp_dir: &str, date: Date) -> Option<HashMap<String, f64>> {
+    let file_name = Path::new(temp_dir).join(dated_filename(date));
+    let file = File::open(&file_name).ok()?;
+
+    let mut content = String::new();
+    let reader = BufReader::new(file);
+    for line in reader.lines() {
+        content.push_str(&line.unwrap_or_default());
+    }
+
+    if content.is_empty() {
+        return None;
+    }
+
+    let json: Value = serde_json::from_str(&content).ok()?;
+    let rates_obj = json.as_object()?.get("rates")?.as_object()?;
+
+    let mut rates = HashMap::new();
+    for (key, val) in rates_obj.iter() {
+        rates.insert(key.clone(), val.as_f64()?);
+    }
+    Some(rates)
+}
+
+/// Summary statistics over a `--timeseries` span.
+#[derive(Clone, Copy, Debug)]
+pub struct SeriesStats {
+    pub min: f64,
+    pub max: f64,
+    pub average: f64,
+}
+
+/// Computes min/max/average over a (non-empty) series of daily rates.
+pub fn summarize(rates: &[f64]) -> Option<SeriesStats> {
+    if rates.is_empty() {
+        return None;
+    }
+
+    let min = rates.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = rates.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let average = rates.iter().sum::<f64>() / rates.len() as f64;
+
+    Some(SeriesStats { min, max, average })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epoch_zero_is_1970_01_01() {
+        let date = Date { year: 1970, month: 1, day: 1 };
+        assert_eq!(date.to_epoch_seconds(), 0);
+    }
+
+    #[test]
+    fn next_rolls_over_a_leap_day_in_february() {
+        let date = Date { year: 2024, month: 2, day: 28 };
+        assert_eq!(date.next(), Date { year: 2024, month: 2, day: 29 });
+        assert_eq!(date.next().next(), Date { year: 2024, month: 3, day: 1 });
+
+        let non_leap = Date { year: 2023, month: 2, day: 28 };
+        assert_eq!(non_leap.next(), Date { year: 2023, month: 3, day: 1 });
+    }
+
+    #[test]
+    fn next_rolls_over_new_years_eve_into_the_next_year() {
+        let date = Date { year: 2023, month: 12, day: 31 };
+        assert_eq!(date.next(), Date { year: 2024, month: 1, day: 1 });
+    }
+
+    #[test]
+    fn epoch_seconds_match_a_known_later_date() {
+        // 2024-01-01T00:00:00Z, a widely-cited reference timestamp.
+        let date = Date { year: 2024, month: 1, day: 1 };
+        assert_eq!(date.to_epoch_seconds(), 1_704_067_200);
+    }
+}
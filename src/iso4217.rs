@@ -0,0 +1,108 @@
+//! ISO 4217 currency metadata.
+//!
+//! The table below mirrors the "isodata.tsv -> generated source" approach
+//! used by crates like `iso_currency`: each row is a currency's alpha-3
+//! code, its English name, its printable symbol, its ISO numeric code and
+//! the number of minor-unit (subunit) decimal places used when formatting
+//! an amount (e.g. `0` for JPY, `2` for most currencies, `3` for some).
+//!
+//! The list only covers the currencies this tool actually deals with
+//! (the ones returned by the exchange rate provider); unknown codes fall
+//! back to sane 2-decimal formatting with no symbol.
+
+#[derive(Clone, Copy, Debug)]
+pub struct CurrencyInfo {
+    pub code: &'static str,
+    pub numeric: u16,
+    pub name: &'static str,
+    pub symbol: &'static str,
+    pub minor_units: u8,
+}
+
+// code, numeric, name, symbol, minor_units
+const TABLE: &[CurrencyInfo] = &[
+    CurrencyInfo { code: "EUR", numeric: 978, name: "Euro", symbol: "€", minor_units: 2 },
+    CurrencyInfo { code: "USD", numeric: 840, name: "US Dollar", symbol: "$", minor_units: 2 },
+    CurrencyInfo { code: "JPY", numeric: 392, name: "Japanese Yen", symbol: "¥", minor_units: 0 },
+    CurrencyInfo { code: "BGN", numeric: 975, name: "Bulgarian Lev", symbol: "лв", minor_units: 2 },
+    CurrencyInfo { code: "CZK", numeric: 203, name: "Czech Koruna", symbol: "Kč", minor_units: 2 },
+    CurrencyInfo { code: "DKK", numeric: 208, name: "Danish Krone", symbol: "kr", minor_units: 2 },
+    CurrencyInfo { code: "GBP", numeric: 826, name: "Pound Sterling", symbol: "£", minor_units: 2 },
+    CurrencyInfo { code: "HUF", numeric: 348, name: "Hungarian Forint", symbol: "Ft", minor_units: 2 },
+    CurrencyInfo { code: "PLN", numeric: 985, name: "Polish Zloty", symbol: "zł", minor_units: 2 },
+    CurrencyInfo { code: "RON", numeric: 946, name: "Romanian Leu", symbol: "lei", minor_units: 2 },
+    CurrencyInfo { code: "SEK", numeric: 752, name: "Swedish Krona", symbol: "kr", minor_units: 2 },
+    CurrencyInfo { code: "CHF", numeric: 756, name: "Swiss Franc", symbol: "CHF", minor_units: 2 },
+    CurrencyInfo { code: "ISK", numeric: 352, name: "Islandic Krona", symbol: "kr", minor_units: 0 },
+    CurrencyInfo { code: "NOK", numeric: 578, name: "Norwegian Krone", symbol: "kr", minor_units: 2 },
+    CurrencyInfo { code: "TRY", numeric: 949, name: "Turkish Lira", symbol: "₺", minor_units: 2 },
+    CurrencyInfo { code: "AUD", numeric: 36, name: "Australian Dollar", symbol: "$", minor_units: 2 },
+    CurrencyInfo { code: "BRL", numeric: 986, name: "Brazilian Real", symbol: "R$", minor_units: 2 },
+    CurrencyInfo { code: "CAD", numeric: 124, name: "Canadian Dollar", symbol: "$", minor_units: 2 },
+    CurrencyInfo { code: "CNY", numeric: 156, name: "Chinese Yuan Renmimbi", symbol: "¥", minor_units: 2 },
+    CurrencyInfo { code: "HKD", numeric: 344, name: "Hong Kong Dollar", symbol: "$", minor_units: 2 },
+    CurrencyInfo { code: "IDR", numeric: 360, name: "Indonesian Rupiah", symbol: "Rp", minor_units: 2 },
+    CurrencyInfo { code: "ILS", numeric: 376, name: "Israeli Shekel", symbol: "₪", minor_units: 2 },
+    CurrencyInfo { code: "INR", numeric: 356, name: "Indian Rupee", symbol: "₹", minor_units: 2 },
+    CurrencyInfo { code: "KRW", numeric: 410, name: "South Korean Won", symbol: "₩", minor_units: 0 },
+    CurrencyInfo { code: "MXN", numeric: 484, name: "Mexican Peso", symbol: "$", minor_units: 2 },
+    CurrencyInfo { code: "MYR", numeric: 458, name: "Malaysian Ringgit", symbol: "RM", minor_units: 2 },
+    CurrencyInfo { code: "NZD", numeric: 554, name: "New Zealand Dollar", symbol: "$", minor_units: 2 },
+    CurrencyInfo { code: "PHP", numeric: 608, name: "Philippine Peso", symbol: "₱", minor_units: 2 },
+    CurrencyInfo { code: "SGD", numeric: 702, name: "Singapore Dollar", symbol: "$", minor_units: 2 },
+    CurrencyInfo { code: "THB", numeric: 764, name: "Thai Baht", symbol: "฿", minor_units: 2 },
+    CurrencyInfo { code: "ZAR", numeric: 710, name: "South African Rand", symbol: "R", minor_units: 2 },
+    // a few extras with a non-standard number of minor units, kept around
+    // because they show up in "all rates" lists from some providers.
+    CurrencyInfo { code: "BHD", numeric: 48, name: "Bahraini Dinar", symbol: "BD", minor_units: 3 },
+    CurrencyInfo { code: "KWD", numeric: 414, name: "Kuwaiti Dinar", symbol: "KD", minor_units: 3 },
+    CurrencyInfo { code: "OMR", numeric: 512, name: "Omani Rial", symbol: "OMR", minor_units: 3 },
+];
+
+/// Looks up the ISO 4217 metadata for a currency code (e.g. `"EUR"`).
+pub fn currency_info(code: &str) -> Option<&'static CurrencyInfo> {
+    TABLE.iter().find(|c| c.code == code)
+}
+
+/// Returns the English name of a currency, or `"Unknown"` if the code
+/// isn't in the table.
+pub fn get_currency_name(code: &str) -> String {
+    currency_info(code)
+        .map(|c| c.name.to_string())
+        .unwrap_or_else(|| String::from("Unknown"))
+}
+
+/// Rounds `amount` to the currency's minor-unit precision and prefixes it
+/// with the currency's symbol (e.g. `format_amount("JPY", 1234.6)` ->
+/// `"¥1235"`, `format_amount("USD", 12.345)` -> `"$12.35"`).
+pub fn format_amount(code: &str, amount: f64) -> String {
+    match currency_info(code) {
+        Some(info) => format!("{}{:.*}", info.symbol, info.minor_units as usize, amount),
+        None => format!("{:.2} {}", amount, code),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_decimal_currency_rounds_to_whole_units() {
+        assert_eq!(format_amount("JPY", 1234.6), "¥1235");
+    }
+
+    #[test]
+    fn two_decimal_currency_rounds_to_cents() {
+        assert_eq!(format_amount("USD", 12.345), "$12.35");
+    }
+
+    #[test]
+    fn three_decimal_currency_rounds_to_mills() {
+        assert_eq!(format_amount("BHD", 1.23456), "BD1.235");
+    }
+
+    #[test]
+    fn unknown_code_falls_back_to_two_decimals_with_no_symbol() {
+        assert_eq!(format_amount("XYZ", 12.345), "12.35 XYZ");
+    }
+}
@@ -1,21 +1,33 @@
 use std::collections::HashMap;
 use std::env;
-use std::fs::File;
-use std::io::{BufReader, BufWriter};
 use std::io::prelude::*;
-use std::path::Path;
-use std::time::{SystemTime, UNIX_EPOCH};
-use curl::easy::Easy;
-use serde_json::Value;
 
-const INET_DL_ADDR: &str = "https://cdn.wahrungsrechner.info/api/latest.json";
-const DEFAULT_FILENAME: &str = "currency.json";
+mod iso4217;
+use iso4217::{currency_info, format_amount};
+
+mod exchange;
+use exchange::{ConversionResult, Exchange};
+
+mod price_extract;
+
+mod history;
+use history::Date;
+
+mod providers;
+
+// synthetic node used for historical snapshots, which (for now) still
+// only give us one implicit base currency rather than a `RateSnapshot`
+// with its own named base; unlikely to ever collide with a real ISO 4217
+// code.
+const BASE_NODE: &str = "__BASE__";
 
 #[derive(Debug)]
 enum ArgumentResult {
     Success,
     ShowUsualList,
     ShowCompleteList,
+    ExtractPrices,
+    ShowTimeSeries,
     ArgumentError,
     //DownloadError,
     //FileError,
@@ -29,6 +41,15 @@ struct ExchangeProcess {
     rate: f64,
     amount_from: f64,
     amount_to: f64,
+    extract_text: String,
+    use_stdin: bool,
+    as_of_date: Option<String>,
+    series_from: String,
+    series_to: String,
+    series_start: String,
+    series_end: String,
+    provider: String,
+    provider_url: Option<String>,
 }
 
 impl ExchangeProcess {
@@ -39,6 +60,15 @@ impl ExchangeProcess {
             rate: 0.0,
             amount_from: 0.0,
             amount_to: 0.0,
+            extract_text: String::new(),
+            use_stdin: false,
+            as_of_date: None,
+            series_from: String::new(),
+            series_to: String::new(),
+            series_start: String::new(),
+            series_end: String::new(),
+            provider: providers::DEFAULT_PROVIDER.to_string(),
+            provider_url: None,
         }
     }
 }
@@ -47,31 +77,56 @@ pub fn run() -> i32 {
 
     let mut rates: HashMap<String, f64> = HashMap::new();
     let mut exchange = ExchangeProcess::new();
-
-    if !check_rates_file() {
-        if !download_rates_file() {
-            eprintln!("Error downloading the currency data.");
-            return 1;
-        }
-    }
-
-    if !load_rates_file_from_disk(&mut rates) {
-        eprintln!("Error loading currency data from disk.");
-        return 2;
-    }
+    let mut base = BASE_NODE.to_string();
 
     let func = parse_arguments(&mut exchange);
     match func {
         ArgumentResult::ArgumentError => return 3,
         ArgumentResult::ShowUsualList => {
+            match ensure_latest_rates(&exchange.provider, exchange.provider_url.as_deref()) {
+                Ok(snapshot) => rates = snapshot.rates,
+                Err(code) => return code,
+            }
             print_usual_rates(&rates);
             return 0;
         }
         ArgumentResult::ShowCompleteList => {
+            match ensure_latest_rates(&exchange.provider, exchange.provider_url.as_deref()) {
+                Ok(snapshot) => rates = snapshot.rates,
+                Err(code) => return code,
+            }
             print_all_rates(&rates);
             return 0;
         }
-        _ => (),
+        ArgumentResult::ExtractPrices => {
+            match ensure_latest_rates(&exchange.provider, exchange.provider_url.as_deref()) {
+                Ok(snapshot) => {
+                    rates = snapshot.rates;
+                    base = snapshot.base;
+                }
+                Err(code) => return code,
+            }
+            return run_price_extraction(&exchange, &rates, &base);
+        }
+        ArgumentResult::ShowTimeSeries => {
+            return run_timeseries(&exchange);
+        }
+        ArgumentResult::Success => (),
+    }
+
+    match &exchange.as_of_date {
+        Some(date) => {
+            if let Err(code) = load_historical_rates(date, &mut rates) {
+                return code;
+            }
+        }
+        None => match ensure_latest_rates(&exchange.provider, exchange.provider_url.as_deref()) {
+            Ok(snapshot) => {
+                rates = snapshot.rates;
+                base = snapshot.base;
+            }
+            Err(code) => return code,
+        },
     }
 
     if !rates.contains_key(&exchange.from) {
@@ -83,179 +138,213 @@ pub fn run() -> i32 {
         return 5;
     }
 
-    exchange.rate = rates[&exchange.to] / rates[&exchange.from];
-    exchange.amount_to = exchange.amount_from * exchange.rate;
-    //dbg!(&exchange);
+    let graph = build_exchange_graph(&rates, &base);
+
+    match graph.get_rate(&exchange.from, &exchange.to) {
+        ConversionResult::Rate { rate, path } => {
+            exchange.rate = rate;
+            exchange.amount_to = exchange.amount_from * exchange.rate;
+            //dbg!(&exchange);
 
-    println!("\x1B[24mActual exchange rate:\x1B[0m \x1B[92m{}\x1B[39m \x1B[93m{:.4}\x1B[39m = \x1B[92m{}\x1B[39m \x1B[93m{:.4}\x1B[39m",
-             exchange.from,
-             exchange.amount_from,
-             exchange.to,
-             exchange.amount_to
-             );
+            println!("\x1B[24mActual exchange rate:\x1B[0m \x1B[92m{}\x1B[39m \x1B[93m{:.4}\x1B[39m = \x1B[92m{}\x1B[39m \x1B[93m{}\x1B[39m",
+                     exchange.from,
+                     exchange.amount_from,
+                     exchange.to,
+                     format_amount(&exchange.to, exchange.amount_to)
+                     );
+
+            if path.len() > 2 {
+                println!("(via {})", path[1..path.len() - 1].join(" -> "));
+            }
+        }
+        ConversionResult::NoPath => {
+            println!("No conversion path found between {} and {}.", exchange.from, exchange.to);
+            return 6;
+        }
+        ConversionResult::ArbitrageDetected(cycle) => {
+            eprintln!("Warning: arbitrage loop detected among rates ({}); refusing to convert.", cycle.join(" -> "));
+            return 7;
+        }
+    }
 
     0
 
 }
 
-fn check_rates_file() -> bool {
-
-    let file_name = Path::new(get_temp_dir().as_str()).join(DEFAULT_FILENAME);
-    if !file_name.exists() {
-        println!("A local copy of {} didn't exist.", file_name.display());
-        return false;
+/// Builds an `Exchange` graph from a flat rates map that shares one
+/// base currency (either a real one from a `RateSnapshot`, or the
+/// synthetic `BASE_NODE` used for historical snapshots), so it can be
+/// combined with rates from other providers (which may use a different
+/// base) and queried with the same multi-hop path search.
+fn build_exchange_graph(rates: &HashMap<String, f64>, base: &str) -> Exchange {
+    let mut graph = Exchange::new();
+    for (code, rate) in rates.iter() {
+        graph.add_or_update_rate(base, code, *rate);
     }
+    graph
+}
 
-    let file = match File::open(&file_name) {
-        Ok(file) => file,
-        Err(err) => {
-            eprintln!("Couldn't open {} (error: {}).", file_name.display(), err);
-            return false
-        },
-    };
+/// Loads the latest rates from `provider_name` (falling back to other
+/// providers, then to a stale cache, as documented on
+/// `providers::load_latest_rates`). `provider_url` is only used by the
+/// `generic` provider, which has no endpoint of its own.
+fn ensure_latest_rates(provider_name: &str, provider_url: Option<&str>) -> Result<providers::RateSnapshot, i32> {
+    providers::load_latest_rates(provider_name, &get_temp_dir(), provider_url).map_err(|err| {
+        eprintln!("Error loading currency data: {}", err);
+        1
+    })
+}
 
-    let metadata = match file.metadata() {
-        Ok(metadata) => metadata,
-        Err(err) => {
-            eprintln!("Couldn't get metadata from file {} (error: {}).", file_name.display(), err);
-            return false
-        },
+/// Loads the historical snapshot for `date_str` (`YYYY-MM-DD`),
+/// downloading and caching it under a date-keyed filename first if
+/// needed.
+fn load_historical_rates(date_str: &str, rates: &mut HashMap<String, f64>) -> Result<(), i32> {
+    let date = match Date::parse(date_str) {
+        Some(date) => date,
+        None => {
+            eprintln!("Invalid --date '{}', expected YYYY-MM-DD.", date_str);
+            return Err(9);
+        }
     };
 
-    let mut file_date: u64 = 0;
-    if let Ok(time) = metadata.modified() {
-        match time.duration_since(UNIX_EPOCH) {
-            Ok(t) => file_date = t.as_secs(),
-            _ => (),
-        }
-    }
+    let temp_dir = get_temp_dir();
 
-    let mut cur_date: u64 = 0;
-    let now = SystemTime::now();
-    match now.duration_since(UNIX_EPOCH) {
-        Ok(t) => cur_date = t.as_secs(),
-        _ => (),
+    if !history::ensure_dated_rates_file(&temp_dir, date) {
+        eprintln!("Error downloading historical currency data for {}.", date);
+        return Err(10);
     }
 
-    if cur_date - file_date >= 3_600 {
-        return false;
+    match history::load_dated_rates(&temp_dir, date) {
+        Some(loaded) => {
+            *rates = loaded;
+            Ok(())
+        }
+        None => {
+            eprintln!("Error loading historical currency data for {}.", date);
+            Err(11)
+        }
     }
-
-    true
-
 }
 
-fn download_rates_file() -> bool {
+/// Handles `--timeseries FROM TO START END`: loads one historical
+/// snapshot per day in `[START, END]`, prints the FROM->TO rate for each
+/// day, and a min/max/average summary at the end.
+fn run_timeseries(exchange: &ExchangeProcess) -> i32 {
 
-    let file_name = Path::new(get_temp_dir().as_str()).join(DEFAULT_FILENAME);
-    let file = match File::create(&file_name) {
-        Ok(file) => file,
-        Err(err) => {
-            eprintln!("Couldn't create {} (error: {}).", file_name.display(), err);
-            return false;
-        },
+    let start = match Date::parse(&exchange.series_start) {
+        Some(date) => date,
+        None => {
+            eprintln!("Invalid START date '{}', expected YYYY-MM-DD.", exchange.series_start);
+            return 9;
+        }
     };
+    let end = match Date::parse(&exchange.series_end) {
+        Some(date) => date,
+        None => {
+            eprintln!("Invalid END date '{}', expected YYYY-MM-DD.", exchange.series_end);
+            return 9;
+        }
+    };
+    if end < start {
+        eprintln!("END date must not be before START date.");
+        return 9;
+    }
 
-    let mut writer = BufWriter::new(file);
-
-    let mut handle = Easy::new();
-    handle.url(INET_DL_ADDR).unwrap();
-
-    let mut transfer = handle.transfer();
-    transfer.write_function(|data| {
-        writer.write_all(data).unwrap();
-        Ok(data.len())
-    }).unwrap();
+    let temp_dir = get_temp_dir();
+    let mut points = Vec::new();
+    let mut date = start;
+
+    loop {
+        if history::ensure_dated_rates_file(&temp_dir, date) {
+            if let Some(rates) = history::load_dated_rates(&temp_dir, date) {
+                let graph = build_exchange_graph(&rates, BASE_NODE);
+                match graph.get_rate(&exchange.series_from, &exchange.series_to) {
+                    ConversionResult::Rate { rate, .. } => {
+                        println!("{}: {:.6}", date, rate);
+                        points.push(rate);
+                    }
+                    _ => println!("{}: no rate available for {} -> {}.", date, exchange.series_from, exchange.series_to),
+                }
+            } else {
+                println!("{}: no snapshot available, skipped.", date);
+            }
+        } else {
+            println!("{}: could not download snapshot, skipped.", date);
+        }
 
-    let _recv = match transfer.perform() {
-        Err(err) => {
-            eprintln!("Error while download: {}", err);
-            return false
+        if date == end {
+            break;
         }
-        Ok(recv) => recv,
-    };
-    //dbg!(&recv);
-    true
+        date = date.next();
+    }
 
+    match history::summarize(&points) {
+        Some(stats) => {
+            println!("\n\x1B[1mSummary {} -> {} from {} to {}:\x1B[0m", exchange.series_from, exchange.series_to, start, end);
+            println!("min: {:.6}  max: {:.6}  average: {:.6}", stats.min, stats.max, stats.average);
+            0
+        }
+        None => {
+            eprintln!("No data points available in the requested range.");
+            12
+        }
+    }
 }
 
-fn load_rates_file_from_disk (exchange_rates: &mut HashMap<String, f64>) -> bool {
+/// Handles `--extract`: reads free text (either already joined from the
+/// command line, or from stdin), pulls out every monetary amount it can
+/// find and converts each one into `exchange.to`.
+fn run_price_extraction(exchange: &ExchangeProcess, rates: &HashMap<String, f64>, base: &str) -> i32 {
 
-    let file_name = Path::new(get_temp_dir().as_str()).join(DEFAULT_FILENAME);
-    let file = match File::open(&file_name) {
-        Ok(file) => file,
-        Err(err) => {
-            eprintln!("Couldn't open {} (error: {}).", file_name.display(), err);
-            return false
-        },
-    };
-
-    let mut content = String::new();
-    let reader = BufReader::new(file);
-    for line in reader.lines() {
+    if !rates.contains_key(&exchange.to) {
+        println!("Did not found currency {}.", exchange.to);
+        return 5;
+    }
 
-        let l = line.unwrap_or_default();
-        content.push_str(&l);
+    let text = if exchange.use_stdin {
+        let mut buf = String::new();
+        if std::io::stdin().read_to_string(&mut buf).is_err() {
+            eprintln!("Error reading text from stdin.");
+            return 8;
+        }
+        buf
+    } else {
+        exchange.extract_text.clone()
+    };
 
+    let prices = price_extract::extract_prices(&text);
+    if prices.is_empty() {
+        println!("No monetary amounts found in the given text.");
+        return 0;
     }
 
-    if content.len() == 0 {
-        eprintln!("File is empty.");
-        return false;
-    }
+    let graph = build_exchange_graph(rates, base);
 
-    let json: Value = serde_json::from_str(&content).unwrap();
-    let rates = json.as_object()
-        .and_then(|object| object.get("rates"))
-        .and_then(|rates| rates.as_object())
-        .unwrap();
+    for price in prices {
+        if !rates.contains_key(&price.currency) {
+            println!("{} ({}): unknown currency, skipped.", price.matched_text, price.currency);
+            continue;
+        }
 
-    for rate in rates.iter() {
-        let key: String = rate.0.to_string();
-        let val: f64 = rate.1.as_f64().unwrap();
-        exchange_rates.insert(key, val);
+        match graph.get_rate(&price.currency, &exchange.to) {
+            ConversionResult::Rate { rate, .. } => {
+                let converted = price.amount * rate;
+                println!("{} ({} {:.2}) = {}", price.matched_text, price.currency, price.amount, format_amount(&exchange.to, converted));
+            }
+            ConversionResult::NoPath => {
+                println!("{}: no conversion path to {}.", price.matched_text, exchange.to);
+            }
+            ConversionResult::ArbitrageDetected(_) => {
+                println!("{}: arbitrage loop detected, skipped.", price.matched_text);
+            }
+        }
     }
 
-    true
+    0
 }
 
 pub fn get_currency_name(currency: &str) -> String {
-    let result: String;
-    match currency {
-        "EUR" => result = "Euro".to_string(),
-        "USD" => result = "US Dollar".to_string(),
-        "JPY" => result = "Japanese Yen".to_string(),
-        "BGN" => result = "Bulgarian Lev".to_string(),
-        "CZK" => result = "Czech Koruna".to_string(),
-        "DKK" => result = "Danish Krone".to_string(),
-        "GBP" => result = "Pound Sterling".to_string(),
-        "HUF" => result = "Hungarian Forint".to_string(),
-        "PLN" => result = "Polish Zloty".to_string(),
-        "RON" => result = "Romanian Leu".to_string(),
-        "SEK" => result = "Swedish Krona".to_string(),
-        "CHF" => result = "Swiss Franc".to_string(),
-        "ISK" => result = "Islandic Krona".to_string(),
-        "NOK" => result = "Norwegian Krone".to_string(),
-        "TRY" => result = "Turkish Lira".to_string(),
-        "AUD" => result = "Australian Dollar".to_string(),
-        "BRL" => result = "Brazilian Real".to_string(),
-        "CAD" => result = "Canadian Dollar".to_string(),
-        "CNY" => result = "Chinese Yuan Renmimbi".to_string(),
-        "HKD" => result = "Hong Kong Dollar".to_string(),
-        "IDR" => result = "Indonesian Rupiah".to_string(),
-        "ILS" => result = "Israeli Shekel".to_string(),
-        "INR" => result = "Indian Rupee".to_string(),
-        "KRW" => result = "South Korean Won".to_string(),
-        "MXN" => result = "Mexican Peso".to_string(),
-        "MYR" => result = "Malaysian Ringgit".to_string(),
-        "NZD" => result = "New Zealand Dollar".to_string(),
-        "PHP" => result = "Philippine Peso".to_string(),
-        "SGD" => result = "Singapore Dollar".to_string(),
-        "THB" => result = "Thai Baht".to_string(),
-        "ZAR" => result = "South African Rand".to_string(),
-        _ => result = String::from("Unknown"),
-    }
-    result
+    iso4217::get_currency_name(currency)
 }
 
 fn get_temp_dir() -> String {
@@ -305,6 +394,76 @@ fn parse_arguments(exchange: &mut ExchangeProcess) -> ArgumentResult {
                 return ArgumentResult::ShowCompleteList;
             }
 
+            "-e" | "--extract" => {
+                let target = match params.next() {
+                    Some(t) => t.to_uppercase(),
+                    None => {
+                        eprintln!("{} requires a target currency, e.g. '--extract USD'.", param);
+                        return ArgumentResult::ArgumentError;
+                    }
+                };
+                exchange.to = target;
+
+                let rest: Vec<String> = params.collect();
+                if rest.iter().any(|p| p == "--stdin") {
+                    exchange.use_stdin = true;
+                } else {
+                    exchange.extract_text = rest.join(" ");
+                }
+
+                return ArgumentResult::ExtractPrices;
+            }
+
+            "--provider" => {
+                match params.next() {
+                    Some(p) => exchange.provider = p,
+                    None => {
+                        eprintln!("--provider requires a name, e.g. '--provider ecb'.");
+                        return ArgumentResult::ArgumentError;
+                    }
+                }
+            }
+
+            "--provider-url" => {
+                match params.next() {
+                    Some(url) => exchange.provider_url = Some(url),
+                    None => {
+                        eprintln!("--provider-url requires a URL, e.g. '--provider-url https://example.com/rates.json'.");
+                        return ArgumentResult::ArgumentError;
+                    }
+                }
+            }
+
+            "--date" => {
+                match params.next() {
+                    Some(d) => exchange.as_of_date = Some(d),
+                    None => {
+                        eprintln!("--date requires a date, e.g. '--date 2024-01-31'.");
+                        return ArgumentResult::ArgumentError;
+                    }
+                }
+            }
+
+            "--timeseries" => {
+                let from = params.next();
+                let to = params.next();
+                let start = params.next();
+                let end = params.next();
+                match (from, to, start, end) {
+                    (Some(from), Some(to), Some(start), Some(end)) => {
+                        exchange.series_from = from.to_uppercase();
+                        exchange.series_to = to.to_uppercase();
+                        exchange.series_start = start;
+                        exchange.series_end = end;
+                        return ArgumentResult::ShowTimeSeries;
+                    }
+                    _ => {
+                        eprintln!("--timeseries requires FROM TO START END, e.g. '--timeseries EUR USD 2024-01-01 2024-01-31'.");
+                        return ArgumentResult::ArgumentError;
+                    }
+                }
+            }
+
             "->" | "=>" | "=" | ">" => {
                 expr += "=";
             }
@@ -373,11 +532,10 @@ fn print_usual_rates(rates: &HashMap<String, f64>) {
 
     println!("\x1B[1mUsual exchange rates:\n---------------------\x1B[0m\n");
 
-    println!(" Abbr| Currency Name\n-----|----------------------");
+    println!(" Abbr| Sym | Num | Currency Name\n-----|-----|-----|----------------------");
     for (key, _) in sorted.iter() {
-        let rate_name = get_currency_name(&key);
-        if rate_name != "Unknown" {
-            println!(" {} | {}", key, rate_name);
+        if let Some(info) = currency_info(key) {
+            println!(" {} | {:<3} | {:<3} | {}", key, info.symbol, info.numeric, info.name);
         }
     }
 
@@ -392,7 +550,10 @@ fn print_all_rates(rates: &HashMap<String, f64>) {
     println!("\x1B[1mAll available exchange rates:\n-----------------------------\x1B[0m\n");
 
     for (key, _) in sorted.iter() {
-        print!("| {} ", key);
+        match currency_info(key) {
+            Some(info) => print!("| {} ({}, {}) ", key, info.symbol, info.numeric),
+            None => print!("| {} ", key),
+        }
     }
     println!("|");
 
@@ -409,6 +570,19 @@ fn print_help(name: &str) {
     println!("-l,  --list        same as '--list-usual'");
     println!("-la, --list-all    list all available currencies (long list)");
     println!("-lu, --list-usual  list the usual currencies for exchange");
+    println!("-e,  --extract     TARGET [TEXT...]  extract prices from free text and");
+    println!("                   convert them into TARGET; add '--stdin' to read the");
+    println!("                   text from standard input instead of the remaining args");
+    println!("     --date        YYYY-MM-DD  use the historical rates for that date");
+    println!("                   instead of the latest ones");
+    println!("     --timeseries  FROM TO START END  print the FROM->TO rate for every");
+    println!("                   day between START and END, plus min/max/average");
+    println!("     --provider    NAME  use this rate provider instead of the default");
+    println!("                   ('{}'); one of 'wahrungsrechner', 'ecb', 'generic'.", providers::DEFAULT_PROVIDER);
+    println!("                   Only affects the latest rates; '--date' and '--timeseries'");
+    println!("                   always fetch their historical snapshots from wahrungsrechner.");
+    println!("     --provider-url  URL  endpoint to use with '--provider generic', which has");
+    println!("                   no endpoint of its own; required for that provider to work");
     println!("-h,  --help        show this help");
     println!("-V,  --version     show the program version and exit");
     println!("");
@@ -424,7 +598,9 @@ fn print_help(name: &str) {
     println!("* For the equality sign '=' you can use an arrow '->' or a greater than '>'.");
     println!("* To define the currencies use their abbrevations. Try '{} --la'", name);
     println!("  if you want a list of all currencies.");
-    println!("* The currencies updated every hour, depending on the file date of the stored");
-    println!("  '{}' file in the system's temporary path.", DEFAULT_FILENAME);
+    println!("* The currencies are refreshed every hour (daily with '--provider ecb'); if every provider is unreachable,");
+    println!("  a stale cache in the system's temporary path is used with a warning.");
+    println!("* '--extract' understands symbols ($, €, £, ...), ISO codes, spelled-out");
+    println!("  currency names and 'k'/'m' magnitude suffixes, e.g. '$1,299.00' or '12.5k GBP'.");
     println!("");
 }
@@ -0,0 +1,181 @@
+//! Free-text price extraction.
+//!
+//! Scans arbitrary text for monetary amounts written the way people
+//! actually write them - `$1,299.00`, `1299 USD`, `€50`, `50 euros`,
+//! `12.5k GBP` - inspired by sesters' price-extraction engine. Detected
+//! amounts can then be converted into a user-chosen target currency by
+//! the caller.
+
+use crate::iso4217;
+
+/// One amount found in free text, in its original currency.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExtractedPrice {
+    pub currency: String,
+    pub amount: f64,
+    pub matched_text: String,
+}
+
+// symbol -> ISO code; reuses the currencies this tool already knows about.
+const SYMBOLS: &[(&str, &str)] = &[
+    ("$", "USD"), ("€", "EUR"), ("£", "GBP"), ("¥", "JPY"), ("₩", "KRW"),
+    ("₹", "INR"), ("₺", "TRY"), ("₪", "ILS"), ("₱", "PHP"), ("฿", "THB"),
+];
+
+// spelled-out currency name (lowercase) -> ISO code
+const NAMES: &[(&str, &str)] = &[
+    ("euro", "EUR"), ("euros", "EUR"),
+    ("dollar", "USD"), ("dollars", "USD"),
+    ("pound", "GBP"), ("pounds", "GBP"), ("sterling", "GBP"),
+    ("yen", "JPY"),
+    ("franc", "CHF"), ("francs", "CHF"),
+    ("krona", "SEK"), ("krone", "NOK"),
+    ("won", "KRW"),
+    ("rupee", "INR"), ("rupees", "INR"),
+    ("lira", "TRY"),
+];
+
+/// Scans `text` and returns every monetary amount it could find, in the
+/// order they appear.
+pub fn extract_prices(text: &str) -> Vec<ExtractedPrice> {
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    let mut results = Vec::new();
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let token = tokens[i];
+
+        if let Some((code, rest)) = strip_symbol_prefix(token) {
+            if let Some(amount) = parse_amount_token(rest) {
+                results.push(ExtractedPrice { currency: code.to_string(), amount, matched_text: token.to_string() });
+                i += 1;
+                continue;
+            }
+        }
+
+        if let Some((code, rest)) = strip_symbol_suffix(token) {
+            if let Some(amount) = parse_amount_token(rest) {
+                results.push(ExtractedPrice { currency: code.to_string(), amount, matched_text: token.to_string() });
+                i += 1;
+                continue;
+            }
+        }
+
+        if let Some(amount) = parse_amount_token(token) {
+            if let Some(next) = tokens.get(i + 1) {
+                if let Some(code) = resolve_currency_word(next) {
+                    results.push(ExtractedPrice {
+                        currency: code,
+                        amount,
+                        matched_text: format!("{} {}", token, next),
+                    });
+                    i += 2;
+                    continue;
+                }
+            }
+        }
+
+        i += 1;
+    }
+
+    results
+}
+
+fn strip_symbol_prefix(token: &str) -> Option<(&'static str, &str)> {
+    SYMBOLS.iter().find_map(|(sym, code)| token.strip_prefix(sym).map(|rest| (*code, rest)))
+}
+
+fn strip_symbol_suffix(token: &str) -> Option<(&'static str, &str)> {
+    SYMBOLS.iter().find_map(|(sym, code)| token.strip_suffix(sym).map(|rest| (*code, rest)))
+}
+
+/// Resolves a word to an ISO 4217 code, either because it already is one
+/// (e.g. `"USD"`) or because it's one of the spelled-out names above
+/// (e.g. `"euros"`). Leading/trailing punctuation (`,`, `.`) is ignored.
+fn resolve_currency_word(word: &str) -> Option<String> {
+    let trimmed = word.trim_matches(|c: char| !c.is_alphabetic());
+
+    let upper = trimmed.to_uppercase();
+    if upper.len() == 3 && iso4217::currency_info(&upper).is_some() {
+        return Some(upper);
+    }
+
+    let lower = trimmed.to_lowercase();
+    NAMES.iter().find(|(name, _)| *name == lower).map(|(_, code)| code.to_string())
+}
+
+/// Parses a number token, accepting `,` as a thousands separator and a
+/// trailing `k`/`m` magnitude suffix (`"12.5k"` -> `12_500.0`). Leading
+/// or trailing sentence punctuation is tolerated, including a sentence-final
+/// `.` (a decimal point is always followed by digits, so a trailing `.` is
+/// never part of the number itself, e.g. `"$1,299.00."`).
+fn parse_amount_token(raw: &str) -> Option<f64> {
+    let mut s = raw.trim_matches(|c: char| c == ',' || c == ';' || c == ':' || c == '!' || c == '?');
+
+    // Only a *trailing* '.' is ever sentence-final punctuation - trimming it
+    // from both ends like the rest would also eat a genuine leading decimal
+    // point, e.g. "$.50" silently becoming 50.0 instead of 0.5.
+    if s.len() > 1 && s.ends_with('.') {
+        s = &s[..s.len() - 1];
+    }
+
+    if s.is_empty() {
+        return None;
+    }
+
+    let mut multiplier = 1.0;
+    if let Some(last) = s.chars().last() {
+        match last.to_ascii_lowercase() {
+            'k' => multiplier = 1_000.0,
+            'm' => multiplier = 1_000_000.0,
+            _ => (),
+        }
+        if multiplier != 1.0 {
+            s = &s[..s.len() - last.len_utf8()];
+        }
+    }
+
+    let cleaned: String = s.chars().filter(|c| *c != ',').collect();
+    if cleaned.is_empty() || !cleaned.chars().any(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    cleaned.parse::<f64>().ok().map(|v| v * multiplier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_price_followed_by_sentence_punctuation() {
+        let prices = extract_prices("The item costs $1,299.00.");
+        assert_eq!(prices, vec![ExtractedPrice {
+            currency: "USD".to_string(),
+            amount: 1299.00,
+            matched_text: "$1,299.00.".to_string(),
+        }]);
+    }
+
+    #[test]
+    fn extracts_mixed_symbol_and_spelled_out_prices() {
+        let prices = extract_prices("It's €50 or 50 euros or 1299 USD or 12.5k GBP");
+        let amounts: Vec<(&str, f64)> = prices.iter().map(|p| (p.currency.as_str(), p.amount)).collect();
+        assert_eq!(amounts, vec![
+            ("EUR", 50.0),
+            ("EUR", 50.0),
+            ("USD", 1299.0),
+            ("GBP", 12_500.0),
+        ]);
+    }
+
+    #[test]
+    fn leading_decimal_point_is_not_mistaken_for_sentence_punctuation() {
+        let prices = extract_prices("$.50 item");
+        assert_eq!(prices, vec![ExtractedPrice {
+            currency: "USD".to_string(),
+            amount: 0.50,
+            matched_text: "$.50".to_string(),
+        }]);
+    }
+}
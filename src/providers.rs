@@ -0,0 +1,415 @@
+//! Exchange rate providers.
+//!
+//! `download_rates_file`/`load_rates_file_from_disk` used to hardwire a
+//! single CDN URL, assume the response always has the exact
+//! `{"rates": {...}}` shape, and `.unwrap()` on anything else. This
+//! module replaces that with a `RateProvider` trait: each implementation
+//! knows its own URL, its own response schema and its own base currency.
+//! `--provider` selects which one to try first; its own snapshot
+//! timestamp (from the JSON body, or the cache file's mtime when the body
+//! has none) is used to decide whether a cached copy has expired. If a download fails, the
+//! other known providers are tried in turn, and if every download fails,
+//! the freshest cache available is used with a warning rather than
+//! giving up.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use curl::easy::Easy;
+use serde_json::Value;
+
+use crate::history::Date;
+
+const FRESHNESS_WINDOW_SECS: u64 = 3_600;
+pub const DEFAULT_PROVIDER: &str = "wahrungsrechner";
+
+/// A parsed rate snapshot: the rates themselves, the provider's own base
+/// currency, and the timestamp the provider says the snapshot is from.
+#[derive(Clone, Debug)]
+pub struct RateSnapshot {
+    pub base: String,
+    pub rates: HashMap<String, f64>,
+    pub timestamp: Option<u64>,
+}
+
+/// A source of exchange rate data: its own download URL, its own
+/// response schema, and its own base currency.
+pub trait RateProvider {
+    /// Short identifier used for `--provider NAME` and cache filenames.
+    fn name(&self) -> &'static str;
+
+    /// URL to download the latest snapshot from.
+    fn url(&self) -> String;
+
+    /// Parses a downloaded response body into a `RateSnapshot`.
+    fn parse(&self, body: &str) -> Result<RateSnapshot, String>;
+
+    /// How long a cached snapshot from this provider stays fresh.
+    /// Providers that only publish once a day (like `EcbStyle`) override
+    /// this to a day, since an hourly window would mark their cache as
+    /// expired minutes after it was written.
+    fn freshness_window_secs(&self) -> u64 {
+        FRESHNESS_WINDOW_SECS
+    }
+}
+
+/// The current wahrungsrechner.info endpoint:
+/// `{"rates": {"USD": 1.08, ...}, "timestamp": 1700000000}`, relative to EUR.
+pub struct Wahrungsrechner;
+
+impl RateProvider for Wahrungsrechner {
+    fn name(&self) -> &'static str {
+        "wahrungsrechner"
+    }
+
+    fn url(&self) -> String {
+        "https://cdn.wahrungsrechner.info/api/latest.json".to_string()
+    }
+
+    fn parse(&self, body: &str) -> Result<RateSnapshot, String> {
+        let json: Value = serde_json::from_str(body).map_err(|err| err.to_string())?;
+        let object = json.as_object().ok_or("response is not a JSON object")?;
+        let rates_obj = object.get("rates")
+            .and_then(|r| r.as_object())
+            .ok_or("missing 'rates' object")?;
+
+        let mut rates = HashMap::new();
+        for (key, val) in rates_obj.iter() {
+            let rate = val.as_f64().ok_or_else(|| format!("rate for {} is not a number", key))?;
+            rates.insert(key.clone(), rate);
+        }
+
+        let timestamp = object.get("timestamp").and_then(|t| t.as_u64());
+
+        Ok(RateSnapshot { base: "EUR".to_string(), rates, timestamp })
+    }
+}
+
+/// An ECB-style endpoint, mirroring the European Central Bank's daily
+/// reference rates format: `{"base": "EUR", "date": "2024-01-31",
+/// "rates": {"USD": 1.08, ...}}`.
+pub struct EcbStyle;
+
+impl RateProvider for EcbStyle {
+    fn name(&self) -> &'static str {
+        "ecb"
+    }
+
+    fn url(&self) -> String {
+        "https://www.ecb.europa.eu/stats/eurofxref/eurofxref-daily.json".to_string()
+    }
+
+    fn freshness_window_secs(&self) -> u64 {
+        // published once a day, so a cache is fresh until the next day's
+        // snapshot would be due rather than after one hour
+        86_400
+    }
+
+    fn parse(&self, body: &str) -> Result<RateSnapshot, String> {
+        let json: Value = serde_json::from_str(body).map_err(|err| err.to_string())?;
+        let object = json.as_object().ok_or("response is not a JSON object")?;
+        let rates_obj = object.get("rates")
+            .and_then(|r| r.as_object())
+            .ok_or("missing 'rates' object")?;
+
+        let mut rates = HashMap::new();
+        for (key, val) in rates_obj.iter() {
+            let rate = val.as_f64().ok_or_else(|| format!("rate for {} is not a number", key))?;
+            rates.insert(key.clone(), rate);
+        }
+
+        let base = object.get("base")
+            .and_then(|b| b.as_str())
+            .unwrap_or("EUR")
+            .to_string();
+
+        let timestamp = object.get("date")
+            .and_then(|d| d.as_str())
+            .and_then(Date::parse)
+            .map(|d| d.to_epoch_seconds());
+
+        Ok(RateSnapshot { base, rates, timestamp })
+    }
+}
+
+/// A generic key/value JSON source: a flat object mapping currency code
+/// directly to its rate, with no envelope and no base currency of its
+/// own (`{"USD": 1.08, "JPY": 160.2, ...}`). There's no endpoint that
+/// serves this shape by default, so `url()` has to be supplied via
+/// `--provider-url`; `GenericKv::default()` points at a placeholder that
+/// can never be reached, the same way an unset `--provider-url` is
+/// reported to the user as a usage error rather than a silent failure.
+pub struct GenericKv {
+    url: String,
+}
+
+impl GenericKv {
+    pub fn new(url: String) -> GenericKv {
+        GenericKv { url }
+    }
+}
+
+impl Default for GenericKv {
+    fn default() -> GenericKv {
+        GenericKv { url: "https://raw.githubusercontent.com/example/rates/main/latest.json".to_string() }
+    }
+}
+
+impl RateProvider for GenericKv {
+    fn name(&self) -> &'static str {
+        "generic"
+    }
+
+    fn url(&self) -> String {
+        self.url.clone()
+    }
+
+    fn parse(&self, body: &str) -> Result<RateSnapshot, String> {
+        let json: Value = serde_json::from_str(body).map_err(|err| err.to_string())?;
+        let object = json.as_object().ok_or("response is not a JSON object")?;
+
+        let mut rates = HashMap::new();
+        for (key, val) in object.iter() {
+            if let Some(rate) = val.as_f64() {
+                rates.insert(key.clone(), rate);
+            }
+        }
+
+        if rates.is_empty() {
+            return Err("no numeric rates found in response".to_string());
+        }
+
+        Ok(RateSnapshot { base: "UNKNOWN".to_string(), rates, timestamp: None })
+    }
+}
+
+fn all_provider_names() -> &'static [&'static str] {
+    &["wahrungsrechner", "ecb", "generic"]
+}
+
+/// Resolves a `--provider` name to a concrete implementation.
+pub fn provider_by_name(name: &str) -> Option<Box<dyn RateProvider>> {
+    provider_by_name_with_url(name, None)
+}
+
+/// Resolves a `--provider` name to a concrete implementation, pointing
+/// `generic` at `url_override` (from `--provider-url`) instead of its
+/// unreachable placeholder URL when one is given.
+fn provider_by_name_with_url(name: &str, url_override: Option<&str>) -> Option<Box<dyn RateProvider>> {
+    match name {
+        "wahrungsrechner" => Some(Box::new(Wahrungsrechner)),
+        "ecb" => Some(Box::new(EcbStyle)),
+        "generic" => Some(Box::new(match url_override {
+            Some(url) => GenericKv::new(url.to_string()),
+            None => GenericKv::default(),
+        })),
+        _ => None,
+    }
+}
+
+fn download_body(url: &str) -> Result<String, String> {
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut handle = Easy::new();
+    handle.url(url).map_err(|err| err.to_string())?;
+
+    {
+        let mut transfer = handle.transfer();
+        transfer.write_function(|data| {
+            buffer.extend_from_slice(data);
+            Ok(data.len())
+        }).map_err(|err| err.to_string())?;
+        transfer.perform().map_err(|err| err.to_string())?;
+    }
+
+    String::from_utf8(buffer).map_err(|err| err.to_string())
+}
+
+fn current_unix_time() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn cache_path(cache_dir: &str, provider_name: &str) -> PathBuf {
+    Path::new(cache_dir).join(format!("currency-{}.json", provider_name))
+}
+
+/// Reads and parses the cached snapshot for a provider, if any, together
+/// with its reference time (the snapshot's own timestamp when present,
+/// falling back to the cache file's mtime otherwise) and whether it has
+/// expired under that provider's freshness window.
+fn read_cached_snapshot(path: &Path, provider: &dyn RateProvider) -> Option<(RateSnapshot, Option<u64>, bool)> {
+    let mut file = File::open(path).ok()?;
+
+    let mtime = file.metadata().ok()
+        .and_then(|m| m.modified().ok())
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs());
+
+    let mut body = String::new();
+    file.read_to_string(&mut body).ok()?;
+    if body.is_empty() {
+        return None;
+    }
+
+    let snapshot = provider.parse(&body).ok()?;
+    let reference_time = snapshot.timestamp.or(mtime);
+    let expired = match reference_time {
+        Some(t) => current_unix_time().saturating_sub(t) >= provider.freshness_window_secs(),
+        None => true,
+    };
+
+    Some((snapshot, reference_time, expired))
+}
+
+fn write_cache(path: &Path, body: &str) {
+    if let Ok(mut file) = File::create(path) {
+        let _ = file.write_all(body.as_bytes());
+    }
+}
+
+/// Loads the latest rates, preferring `preferred_name`. If that
+/// provider's own cache is still fresh, it's used as-is. Otherwise a
+/// fresh download is attempted, first from the preferred provider and
+/// then from every other known provider in turn; if every download
+/// fails, the freshest cache available (even if stale) is used with a
+/// warning instead of giving up. `provider_url` (from `--provider-url`)
+/// points the `generic` provider at a real endpoint; without it, `generic`
+/// can't be reached and is rejected up front instead of failing later with
+/// a confusing network error against its placeholder URL.
+pub fn load_latest_rates(preferred_name: &str, cache_dir: &str, provider_url: Option<&str>) -> Result<RateSnapshot, String> {
+    if preferred_name == "generic" && provider_url.is_none() {
+        return Err("provider 'generic' has no endpoint of its own; pass one with --provider-url.".to_string());
+    }
+
+    let preferred = provider_by_name_with_url(preferred_name, provider_url)
+        .ok_or_else(|| format!("Unknown provider '{}'.", preferred_name))?;
+
+    if let Some((snapshot, _, expired)) = read_cached_snapshot(&cache_path(cache_dir, preferred.name()), preferred.as_ref()) {
+        if !expired {
+            return Ok(snapshot);
+        }
+    }
+
+    let mut order: Vec<&str> = vec![preferred.name()];
+    order.extend(all_provider_names().iter().filter(|name| **name != preferred.name()));
+
+    for name in &order {
+        let provider = if *name == preferred.name() {
+            provider_by_name_with_url(name, provider_url)
+        } else {
+            provider_by_name(name)
+        }.expect("name comes from all_provider_names/provider_by_name");
+        match download_body(&provider.url()) {
+            Ok(body) => match provider.parse(&body) {
+                Ok(snapshot) => {
+                    write_cache(&cache_path(cache_dir, provider.name()), &body);
+                    if *name != preferred_name {
+                        eprintln!("Warning: provider '{}' unavailable, used '{}' instead.", preferred_name, name);
+                    }
+                    return Ok(snapshot);
+                }
+                Err(err) => eprintln!("Provider '{}' returned an unusable response: {}", name, err),
+            },
+            Err(err) => eprintln!("Provider '{}' download failed: {}", name, err),
+        }
+    }
+
+    // every download failed; fall back to the freshest cache we have across
+    // all providers, even if it's stale, rather than just the first one found
+    let mut candidates = Vec::new();
+    for name in &order {
+        let provider = provider_by_name(name).expect("name comes from all_provider_names/provider_by_name");
+        if let Some((snapshot, reference_time, _)) = read_cached_snapshot(&cache_path(cache_dir, name), provider.as_ref()) {
+            candidates.push((*name, snapshot, reference_time));
+        }
+    }
+
+    match pick_freshest(candidates) {
+        Some((name, snapshot)) => {
+            eprintln!("Warning: all providers unreachable, using stale cached rates from '{}'.", name);
+            Ok(snapshot)
+        }
+        None => Err("no provider reachable and no usable cache available".to_string()),
+    }
+}
+
+/// Picks the candidate with the most recent reference time, treating a
+/// missing reference time as older than any known one.
+fn pick_freshest(candidates: Vec<(&str, RateSnapshot, Option<u64>)>) -> Option<(&str, RateSnapshot)> {
+    let mut best: Option<(&str, RateSnapshot, Option<u64>)> = None;
+    for candidate in candidates {
+        let is_newer = match &best {
+            Some((_, _, best_time)) => candidate.2 > *best_time,
+            None => true,
+        };
+        if is_newer {
+            best = Some(candidate);
+        }
+    }
+    best.map(|(name, snapshot, _)| (name, snapshot))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    struct DummyProvider {
+        window: u64,
+    }
+
+    impl RateProvider for DummyProvider {
+        fn name(&self) -> &'static str {
+            "dummy"
+        }
+        fn url(&self) -> String {
+            String::new()
+        }
+        fn parse(&self, body: &str) -> Result<RateSnapshot, String> {
+            let timestamp = body.trim().parse().map_err(|_| "bad timestamp".to_string())?;
+            Ok(RateSnapshot { base: "EUR".to_string(), rates: HashMap::new(), timestamp: Some(timestamp) })
+        }
+        fn freshness_window_secs(&self) -> u64 {
+            self.window
+        }
+    }
+
+    #[test]
+    fn expiry_check_uses_the_providers_own_freshness_window() {
+        let path = std::env::temp_dir().join("currency-test-expiry.json");
+        let two_hours_old = current_unix_time().saturating_sub(7_200);
+        fs::write(&path, two_hours_old.to_string()).expect("write temp cache file");
+
+        let hourly = DummyProvider { window: 3_600 };
+        let (_, _, expired) = read_cached_snapshot(&path, &hourly).expect("cache should parse");
+        assert!(expired, "a 2-hour-old snapshot should be expired under an hourly window");
+
+        let daily = DummyProvider { window: 86_400 };
+        let (_, _, expired) = read_cached_snapshot(&path, &daily).expect("cache should parse");
+        assert!(!expired, "a 2-hour-old snapshot should still be fresh under a daily window");
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn pick_freshest_prefers_the_most_recent_timestamp() {
+        let stale = RateSnapshot { base: "EUR".to_string(), rates: HashMap::new(), timestamp: Some(1_000) };
+        let fresh = RateSnapshot { base: "EUR".to_string(), rates: HashMap::new(), timestamp: Some(5_000) };
+
+        let candidates = vec![
+            ("wahrungsrechner", stale, Some(1_000)),
+            ("ecb", fresh, Some(5_000)),
+        ];
+
+        let (name, snapshot) = pick_freshest(candidates).expect("a candidate should be picked");
+        assert_eq!(name, "ecb");
+        assert_eq!(snapshot.timestamp, Some(5_000));
+    }
+
+    #[test]
+    fn pick_freshest_returns_none_for_no_candidates() {
+        assert!(pick_freshest(Vec::new()).is_none());
+    }
+}